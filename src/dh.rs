@@ -0,0 +1,96 @@
+//! `dh` provides ephemeral and static Diffie-Hellman key agreement over
+//! the same Ristretto keys used elsewhere in this crate, in the style of
+//! ristretto255-dh, so users can derive a shared symmetric key without
+//! dropping to the lower-level dalek API.
+
+use rand_core::{RngCore, CryptoRng};
+
+use crate::{PrivateKey, PublicKey};
+
+/// `SharedSecret` is the output of a Diffie-Hellman key agreement: the
+/// compressed point `sk*their_pk`. It is not a symmetric key by itself
+/// and should be run through a KDF before use.
+pub struct SharedSecret([u8; 32]);
+
+impl SharedSecret {
+    /// `to_bytes` returns the `SharedSecret` as an array of bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl PrivateKey {
+    /// `diffie_hellman` computes the Diffie-Hellman `SharedSecret` between
+    /// this `PrivateKey` and `their_public`.
+    pub fn diffie_hellman(&self, their_public: &PublicKey) -> Result<SharedSecret, String> {
+        let their_point = their_public.to_point().decompress()
+            .ok_or_else(|| "invalid public key".to_string())?;
+
+        let shared = their_point * self.to_scalar();
+        Ok(SharedSecret(shared.compress().to_bytes()))
+    }
+}
+
+/// `EphemeralSecret` wraps a `PrivateKey` that is meant to be used for a
+/// single Diffie-Hellman exchange and then discarded, mirroring
+/// x25519-dalek's ephemeral key type.
+pub struct EphemeralSecret(PrivateKey);
+
+impl EphemeralSecret {
+    /// `new` creates a new random `EphemeralSecret`.
+    pub fn new() -> Result<EphemeralSecret, String> {
+        let private = PrivateKey::new()?;
+        Ok(EphemeralSecret(private))
+    }
+
+    /// `from_rng` creates a new random `EphemeralSecret`, but requires
+    /// to specify a random generator.
+    pub fn from_rng<R>(rng: &mut R) -> Result<EphemeralSecret, String>
+        where R: RngCore + CryptoRng
+    {
+        let private = PrivateKey::from_rng(rng)?;
+        Ok(EphemeralSecret(private))
+    }
+
+    /// `public_key` returns the `PublicKey` to send to the other party.
+    pub fn public_key(&self) -> PublicKey {
+        self.0.to_public()
+    }
+
+    /// `diffie_hellman` consumes the `EphemeralSecret` to compute the
+    /// `SharedSecret` with `their_public`, so it cannot be reused for a
+    /// second exchange.
+    pub fn diffie_hellman(self, their_public: &PublicKey) -> Result<SharedSecret, String> {
+        self.0.diffie_hellman(their_public)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrivateKey;
+
+    #[test]
+    fn diffie_hellman_agrees_both_ways() {
+        let a = PrivateKey::new().unwrap();
+        let b = PrivateKey::new().unwrap();
+
+        let a_shared = a.diffie_hellman(&b.to_public()).unwrap();
+        let b_shared = b.diffie_hellman(&a.to_public()).unwrap();
+
+        assert_eq!(a_shared.to_bytes(), b_shared.to_bytes());
+    }
+
+    #[test]
+    fn ephemeral_secret_agrees_with_static_key() {
+        let ephemeral = EphemeralSecret::new().unwrap();
+        let ephemeral_public = ephemeral.public_key();
+
+        let their_static = PrivateKey::new().unwrap();
+
+        let ephemeral_shared = ephemeral.diffie_hellman(&their_static.to_public()).unwrap();
+        let static_shared = their_static.diffie_hellman(&ephemeral_public).unwrap();
+
+        assert_eq!(ephemeral_shared.to_bytes(), static_shared.to_bytes());
+    }
+}
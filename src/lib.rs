@@ -1,11 +1,141 @@
+#[macro_use]
+extern crate lazy_static;
+
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::ristretto::{RistrettoPoint, CompressedRistretto};
-use curve25519_dalek::constants::{BASEPOINT_ORDER, RISTRETTO_BASEPOINT_TABLE};
+use curve25519_dalek::constants::{RISTRETTO_BASEPOINT_POINT, RISTRETTO_BASEPOINT_TABLE};
+use curve25519_dalek::traits::Identity;
 use digest::Digest;
 use typenum::consts::U64;
-use rand_core::{RngCore, CryptoRng};
-use rand_os::OsRng;
+use rand_core::{RngCore, CryptoRng, OsRng};
 use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+use serde::de::{Error as DeError, SeqAccess, Visitor};
+use std::collections::HashMap;
+use std::ops::Add;
+use std::fmt;
+use std::str::FromStr;
+
+pub mod twisted;
+pub mod proof;
+pub mod dh;
+pub mod threshold;
+
+/// `Bytes32Visitor` deserializes a 32-byte array, accepting both the
+/// `visit_bytes`/`visit_byte_buf` shape that binary formats give
+/// `serialize_bytes`, and the `visit_seq` shape that self-describing
+/// formats like JSON give it instead.
+struct Bytes32Visitor;
+
+impl<'de> Visitor<'de> for Bytes32Visitor {
+    type Value = [u8; 32];
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "32 bytes")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<[u8; 32], E>
+        where E: DeError
+    {
+        if v.len() != 32 {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(v);
+        Ok(buf)
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<[u8; 32], E>
+        where E: DeError
+    {
+        self.visit_bytes(&v)
+    }
+
+    // Self-describing formats like JSON have no native byte-string type, so
+    // `serialize_bytes` round-trips as a sequence of `u8`s instead; accept
+    // that shape too rather than only the `visit_bytes`/`visit_byte_buf`
+    // path that binary formats take.
+    fn visit_seq<A>(self, mut seq: A) -> Result<[u8; 32], A::Error>
+        where A: SeqAccess<'de>
+    {
+        let mut buf = [0u8; 32];
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = seq.next_element()?
+                .ok_or_else(|| DeError::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(DeError::invalid_length(33, &self));
+        }
+        Ok(buf)
+    }
+}
+
+/// `deserialize_bytes32` deserializes a 32-byte array via
+/// `deserialize_bytes`/`Bytes32Visitor`, the counterpart to
+/// `serializer.serialize_bytes` used by `Message`, `PrivateKey` and
+/// `PublicKey`.
+fn deserialize_bytes32<'de, D>(deserializer: D) -> Result<[u8; 32], D::Error>
+    where D: Deserializer<'de>
+{
+    deserializer.deserialize_bytes(Bytes32Visitor)
+}
+
+/// `Bytes64Visitor` deserializes a 64-byte array, the `CypherText`-sized
+/// counterpart to `Bytes32Visitor`.
+struct Bytes64Visitor;
+
+impl<'de> Visitor<'de> for Bytes64Visitor {
+    type Value = [u8; 64];
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "64 bytes")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<[u8; 64], E>
+        where E: DeError
+    {
+        if v.len() != 64 {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+
+        let mut buf = [0u8; 64];
+        buf.copy_from_slice(v);
+        Ok(buf)
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<[u8; 64], E>
+        where E: DeError
+    {
+        self.visit_bytes(&v)
+    }
+
+    // See `Bytes32Visitor::visit_seq`: self-describing formats encode
+    // `serialize_bytes` as a sequence rather than a native byte string.
+    fn visit_seq<A>(self, mut seq: A) -> Result<[u8; 64], A::Error>
+        where A: SeqAccess<'de>
+    {
+        let mut buf = [0u8; 64];
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = seq.next_element()?
+                .ok_or_else(|| DeError::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(DeError::invalid_length(65, &self));
+        }
+        Ok(buf)
+    }
+}
+
+/// `deserialize_bytes64` deserializes a 64-byte array via
+/// `deserialize_bytes`/`Bytes64Visitor`, the counterpart to
+/// `serializer.serialize_bytes` used by `CypherText`.
+fn deserialize_bytes64<'de, D>(deserializer: D) -> Result<[u8; 64], D::Error>
+    where D: Deserializer<'de>
+{
+    deserializer.deserialize_bytes(Bytes64Visitor)
+}
 
 /// `Message` is an ElGamal message.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
@@ -19,8 +149,7 @@ impl Message {
 
     /// `random` creates a new random `Message`.
     pub fn random() -> Result<Message, String> {
-        let mut rng = OsRng::new()
-            .map_err(|e| format!("{}", e))?;
+        let mut rng = OsRng;
 
         let msg = Message::from_rng(&mut rng);
         Ok(msg)
@@ -52,20 +181,91 @@ impl Message {
     pub fn to_point(&self) -> CompressedRistretto {
         CompressedRistretto::from_slice(&self.0[..])
     }
+
+    /// `to_bytes` returns the `Message` as an array of bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl Serialize for Message {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D>(deserializer: D) -> Result<Message, D::Error>
+        where D: Deserializer<'de>
+    {
+        let bytes = deserialize_bytes32(deserializer)?;
+        let point = CompressedRistretto::from_slice(&bytes);
+        if point.decompress().is_none() {
+            return Err(DeError::custom("invalid point"));
+        }
+
+        Ok(Message::from_point(&point))
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl FromStr for Message {
+    type Err = String;
+
+    /// `from_str` creates a new `Message` from a hex-encoded, canonical
+    /// `CompressedRistretto`.
+    fn from_str(s: &str) -> Result<Message, String> {
+        let bytes = hex::decode(s).map_err(|e| format!("{}", e))?;
+        if bytes.len() != 32 {
+            return Err("invalid length".into());
+        }
+
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(&bytes);
+
+        let point = CompressedRistretto::from_slice(&buf);
+        if point.decompress().is_none() {
+            return Err("invalid point".into());
+        }
+
+        Ok(Message::from_point(&point))
+    }
 }
 
 /// `PrivateKey` is an ElGamal private key. It's just a
 /// wrapper around `Scalar`. The key is just an integer
 /// between 1 and q-1, where q is the order of the group
 /// G.
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
 pub struct PrivateKey(Scalar);
 
+impl fmt::Debug for PrivateKey {
+    /// Redacts the scalar, so an errant `{:?}` doesn't leak key material.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("PrivateKey(..)")
+    }
+}
+
+impl Eq for PrivateKey {}
+
+impl PartialEq for PrivateKey {
+    /// Constant-time equality, to avoid leaking key bits through timing.
+    fn eq(&self, other: &PrivateKey) -> bool {
+        self.0.ct_eq(&other.0).unwrap_u8() == 1u8
+    }
+}
+
 impl PrivateKey {
     /// `new` creates a new random `PrivateKey`.
     pub fn new() -> Result<PrivateKey, String> {
-        let mut rng = OsRng::new()
-            .map_err(|e| format!("{}", e))?;
+        let mut rng = OsRng;
 
         PrivateKey::from_rng(&mut rng)
     }
@@ -130,6 +330,47 @@ impl PrivateKey {
     }
 }
 
+impl Serialize for PrivateKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+impl<'de> Deserialize<'de> for PrivateKey {
+    fn deserialize<D>(deserializer: D) -> Result<PrivateKey, D::Error>
+        where D: Deserializer<'de>
+    {
+        let bytes = deserialize_bytes32(deserializer)?;
+        PrivateKey::from_slice(bytes).map_err(DeError::custom)
+    }
+}
+
+impl fmt::Display for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.to_bytes()))
+    }
+}
+
+impl FromStr for PrivateKey {
+    type Err = String;
+
+    /// `from_str` creates a new `PrivateKey` from a hex-encoded,
+    /// canonical `Scalar`.
+    fn from_str(s: &str) -> Result<PrivateKey, String> {
+        let bytes = hex::decode(s).map_err(|e| format!("{}", e))?;
+        if bytes.len() != 32 {
+            return Err("invalid length".into());
+        }
+
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(&bytes);
+
+        PrivateKey::from_slice(buf)
+    }
+}
+
 /// `PublicKey` is an ElGamal public key. It's just a
 /// wrapper around `CompressedRistretto`.
 /// The key is computed as g^x, where g is the generator
@@ -178,8 +419,59 @@ impl PublicKey {
     }
 }
 
+impl Serialize for PublicKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D>(deserializer: D) -> Result<PublicKey, D::Error>
+        where D: Deserializer<'de>
+    {
+        let bytes = deserialize_bytes32(deserializer)?;
+        let point = CompressedRistretto::from_slice(&bytes);
+        if point.decompress().is_none() {
+            return Err(DeError::custom("invalid point"));
+        }
+
+        Ok(PublicKey::from_point(point))
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.to_bytes()))
+    }
+}
+
+impl FromStr for PublicKey {
+    type Err = String;
+
+    /// `from_str` creates a new `PublicKey` from a hex-encoded,
+    /// decompressable `CompressedRistretto`.
+    fn from_str(s: &str) -> Result<PublicKey, String> {
+        let bytes = hex::decode(s).map_err(|e| format!("{}", e))?;
+        if bytes.len() != 32 {
+            return Err("invalid length".into());
+        }
+
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(&bytes);
+
+        let point = CompressedRistretto::from_slice(&buf);
+        if point.decompress().is_none() {
+            return Err("invalid point".into());
+        }
+
+        Ok(PublicKey::from_point(point))
+    }
+}
+
 /// `KeyPair` is a pair of ElGamal `PublicKey` and `PrivateKey`.
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct KeyPair {
     pub public_key: PublicKey,
     pub private_key: PrivateKey,
@@ -244,27 +536,122 @@ pub struct CypherText {
     pub delta: CompressedRistretto,
 }
 
-/// `encrypt` encrypts a `Message` into a `CypherText`.
-pub fn encrypt(msg: Message, pk: PublicKey, sk: PrivateKey) -> Result<CypherText, String> {
-    // s  = pk.to_point() * sk.to_scalar()
-    // c1 = RISTRETTO_BASEPOINT_TABLE * sk.to_scalar()
-    // c2 = m.to_point() * s
-    // (c1, c2)
-    if sk.to_public().to_point().ct_eq(&pk.to_point()).unwrap_u8() == 1u8 {
-        return Err("same private keys".into());
+impl CypherText {
+    /// `to_bytes` returns the `CypherText` as 64 bytes: `gamma` and
+    /// `delta`, each 32 bytes, concatenated in that order.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut buf = [0u8; 64];
+        buf[..32].copy_from_slice(self.gamma.as_bytes());
+        buf[32..].copy_from_slice(self.delta.as_bytes());
+        buf
+    }
+
+    /// `from_slice` creates a new `CypherText` from 64 bytes, as produced
+    /// by `to_bytes`.
+    pub fn from_slice(buf: &[u8]) -> Result<CypherText, String> {
+        if buf.len() != 64 {
+            return Err("invalid length".into());
+        }
+
+        let gamma = CompressedRistretto::from_slice(&buf[..32]);
+        let delta = CompressedRistretto::from_slice(&buf[32..]);
+
+        if gamma.decompress().is_none() {
+            return Err("invalid gamma".into());
+        }
+        if delta.decompress().is_none() {
+            return Err("invalid delta".into());
+        }
+
+        Ok(CypherText { gamma, delta })
+    }
+}
+
+impl Serialize for CypherText {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+impl<'de> Deserialize<'de> for CypherText {
+    fn deserialize<D>(deserializer: D) -> Result<CypherText, D::Error>
+        where D: Deserializer<'de>
+    {
+        let bytes = deserialize_bytes64(deserializer)?;
+        CypherText::from_slice(&bytes).map_err(DeError::custom)
+    }
+}
+
+impl fmt::Display for CypherText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.to_bytes()))
+    }
+}
+
+impl FromStr for CypherText {
+    type Err = String;
+
+    /// `from_str` creates a new `CypherText` from its hex-encoded,
+    /// 64-byte `to_bytes` representation.
+    fn from_str(s: &str) -> Result<CypherText, String> {
+        let bytes = hex::decode(s).map_err(|e| format!("{}", e))?;
+        CypherText::from_slice(&bytes)
     }
+}
+
+/// `encrypt` encrypts a `Message` into a `CypherText`.
+///
+/// A fresh, nonzero ephemeral `Scalar` is sampled from `OsRng` for every
+/// call, so the same `Message`/`PublicKey` pair never produces the same
+/// `CypherText` twice. Reusing a `PrivateKey` as the ephemeral exponent, as
+/// a previous version of this function did, breaks semantic security if
+/// that scalar is ever reused elsewhere, so this function no longer takes
+/// a `PrivateKey` at all.
+pub fn encrypt(msg: Message, pk: PublicKey) -> Result<CypherText, String> {
+    let mut rng = OsRng;
+
+    encrypt_with(msg, pk, &mut rng)
+}
 
+/// `encrypt_with` encrypts a `Message` into a `CypherText`, but requires
+/// to specify a random generator for the ephemeral `Scalar`. This mirrors
+/// the `from_rng` constructors on the key types, and is useful for
+/// deterministic or testable randomness.
+pub fn encrypt_with<R>(msg: Message, pk: PublicKey, rng: &mut R) -> Result<CypherText, String>
+    where R: RngCore + CryptoRng
+{
+    let (cyph, _k) = encrypt_with_randomness(msg, pk, rng)?;
+    Ok(cyph)
+}
+
+/// `encrypt_with_randomness` is like `encrypt_with`, but also returns the
+/// ephemeral `Scalar` `k` it sampled. Callers that need to later prove
+/// the `CypherText` was formed correctly, via `proof::prove_encryption`,
+/// need this `k`; plain encryption should prefer `encrypt`/`encrypt_with`.
+pub fn encrypt_with_randomness<R>(msg: Message, pk: PublicKey, mut rng: &mut R) -> Result<(CypherText, Scalar), String>
+    where R: RngCore + CryptoRng
+{
+    // k  = random nonzero scalar
+    // c1 = RISTRETTO_BASEPOINT_TABLE * k
+    // c2 = m.to_point() + pk.to_point() * k
+    // (c1, c2)
     if let Some(pk_point) = pk.to_point().decompress() {
         if let Some(msg_point) = msg.to_point().decompress() {
-            let sk_scalar = sk.to_scalar();
-            let shared = pk_point * sk_scalar;
-            let gamma_decomp = &RISTRETTO_BASEPOINT_TABLE * &sk_scalar;
+            let mut k = Scalar::random(&mut rng);
+            while k.ct_eq(&Scalar::zero()).unwrap_u8() == 1u8 {
+                k = Scalar::random(&mut rng);
+            }
+
+            let shared = pk_point * k;
+            let gamma_decomp = &RISTRETTO_BASEPOINT_TABLE * &k;
             let delta_decomp = msg_point + shared;
             let gamma = gamma_decomp.compress();
             let delta = delta_decomp.compress();
 
             let cyph = CypherText { gamma, delta };
-            Ok(cyph)
+            Ok((cyph, k))
         } else {
             Err("invalid message".into())
         }
@@ -275,15 +662,13 @@ pub fn encrypt(msg: Message, pk: PublicKey, sk: PrivateKey) -> Result<CypherText
 
 /// `decrypt` decrypts a `CypherText` into a `Message`.
 pub fn decrypt(cyph: CypherText, sk: PrivateKey) -> Result<Message, String> {
-    // s  = cyph.c1.to_point() * sk.to_scalar() [unused as we use the Lagrange Theorem]
-    // s' = cyph.c1.to_point() * (Scalar::from(ORDER)- Scalar::one(sk.to_scalar() - sk.to_scalar())
-    // m  = c2.to_point() * s'.to_point()
-    // m
+    // gamma = k*G
+    // delta = m + pk*k = m + (sk*G)*k
+    // m     = delta - sk*gamma
     if let Some(gamma_point) = cyph.gamma.decompress() {
         if let Some(delta_point) = cyph.delta.decompress() {
-            let sk_scalar = sk.to_scalar();
-            let inv_shared = gamma_point * (BASEPOINT_ORDER - Scalar::one() - sk_scalar);
-            let msg_point = delta_point - inv_shared;
+            let shared = gamma_point * sk.to_scalar();
+            let msg_point = delta_point - shared;
 
             let msg = Message::from_point(&msg_point.compress());
             Ok(msg)
@@ -294,3 +679,262 @@ pub fn decrypt(cyph: CypherText, sk: PrivateKey) -> Result<Message, String> {
         Err("invalid gamma".into())
     }
 }
+
+/// `DEFAULT_AMOUNT_BOUND` is the default upper bound on amounts supported
+/// by `decrypt_amount`. It matches the size of a `u32`, which is what
+/// Solana's zk-token-sdk uses for confidential transfer amounts.
+pub const DEFAULT_AMOUNT_BOUND: u64 = 1u64 << 32;
+
+lazy_static! {
+    static ref BABY_STEPS: HashMap<[u8; 32], u64> =
+        baby_step_table(baby_step_count(DEFAULT_AMOUNT_BOUND));
+}
+
+/// `baby_step_count` returns `m = ceil(sqrt(bound))`, the number of baby
+/// steps (and giant steps) needed to cover `0..bound` in the baby-step
+/// giant-step discrete log search.
+fn baby_step_count(bound: u64) -> u64 {
+    (bound as f64).sqrt().ceil() as u64
+}
+
+/// `baby_step_table` builds the `{ (j*G).compress().to_bytes() -> j }`
+/// lookup table used by the baby-step giant-step discrete log search,
+/// for `j` in `0..m`.
+fn baby_step_table(m: u64) -> HashMap<[u8; 32], u64> {
+    let mut table = HashMap::with_capacity(m as usize);
+    let mut point = RistrettoPoint::identity();
+    for j in 0..m {
+        table.insert(point.compress().to_bytes(), j);
+        point += RISTRETTO_BASEPOINT_POINT;
+    }
+    table
+}
+
+/// `encrypt_amount` encrypts a `u64` amount into a `CypherText`, mapping
+/// the amount to `amount*G` before the usual ElGamal encryption. Unlike
+/// `encrypt`, the resulting `CypherText` is additively homomorphic: two
+/// `CypherText`s produced by `encrypt_amount` can be summed with `+`, and
+/// the sum decrypts (via `decrypt_amount`) to the sum of the amounts.
+pub fn encrypt_amount(amount: u64, pk: PublicKey) -> Result<CypherText, String> {
+    let amount_point = (&Scalar::from(amount) * &RISTRETTO_BASEPOINT_TABLE).compress();
+    let msg = Message::from_point(&amount_point);
+    encrypt(msg, pk)
+}
+
+/// `decrypt_amount` decrypts a `CypherText` produced by `encrypt_amount`
+/// back into its `u64` amount, solving the discrete log via baby-step
+/// giant-step. Returns `None` if the amount is `DEFAULT_AMOUNT_BOUND` or
+/// more.
+pub fn decrypt_amount(cyph: CypherText, sk: PrivateKey) -> Option<u64> {
+    decrypt_amount_bounded(cyph, sk, DEFAULT_AMOUNT_BOUND)
+}
+
+/// `decrypt_amount_bounded` is like `decrypt_amount`, but allows
+/// configuring the (exclusive) upper bound on the supported amount. A
+/// `bound` of exactly `DEFAULT_AMOUNT_BOUND` reuses a lazily-initialized,
+/// cached baby-step table; any other bound, smaller or larger, builds its
+/// own table on the fly.
+pub fn decrypt_amount_bounded(cyph: CypherText, sk: PrivateKey, bound: u64) -> Option<u64> {
+    let msg = decrypt(cyph, sk).ok()?;
+    let point = msg.to_point().decompress()?;
+    solve_discrete_log_bounded(point, bound)
+}
+
+/// `solve_discrete_log_bounded` recovers `amount` from `amount*G` via
+/// baby-step giant-step, for `amount` in `0..bound`. Shared by
+/// `decrypt_amount_bounded` and the `twisted` module, which both need to
+/// recover an amount encoded as a point multiple of the Ristretto
+/// basepoint.
+pub(crate) fn solve_discrete_log_bounded(mut point: RistrettoPoint, bound: u64) -> Option<u64> {
+    let m = baby_step_count(bound);
+    let giant_step = &Scalar::from(m) * &RISTRETTO_BASEPOINT_TABLE;
+
+    let owned_table;
+    let table: &HashMap<[u8; 32], u64> = if bound == DEFAULT_AMOUNT_BOUND {
+        &BABY_STEPS
+    } else {
+        owned_table = baby_step_table(m);
+        &owned_table
+    };
+
+    for i in 0..m {
+        if let Some(&j) = table.get(&point.compress().to_bytes()) {
+            let amount = i * m + j;
+            if amount < bound {
+                return Some(amount);
+            }
+        }
+        point -= giant_step;
+    }
+
+    None
+}
+
+impl Add for CypherText {
+    type Output = Result<CypherText, String>;
+
+    /// Componentwise addition of two `CypherText`s. Valid only for
+    /// `CypherText`s produced under the same `PublicKey` by
+    /// `encrypt_amount`: the sum decrypts to the sum of the amounts.
+    fn add(self, other: CypherText) -> Result<CypherText, String> {
+        let gamma = self.gamma.decompress().ok_or_else(|| "invalid gamma".to_string())?;
+        let delta = self.delta.decompress().ok_or_else(|| "invalid delta".to_string())?;
+        let other_gamma = other.gamma.decompress().ok_or_else(|| "invalid gamma".to_string())?;
+        let other_delta = other.delta.decompress().ok_or_else(|| "invalid delta".to_string())?;
+
+        Ok(CypherText {
+            gamma: (gamma + other_gamma).compress(),
+            delta: (delta + other_delta).compress(),
+        })
+    }
+}
+
+impl std::ops::Mul<Scalar> for CypherText {
+    type Output = Result<CypherText, String>;
+
+    /// Scalar multiplication of a `CypherText` produced by
+    /// `encrypt_amount`: the result decrypts to the amount multiplied
+    /// by `scalar`.
+    fn mul(self, scalar: Scalar) -> Result<CypherText, String> {
+        let gamma = self.gamma.decompress().ok_or_else(|| "invalid gamma".to_string())?;
+        let delta = self.delta.decompress().ok_or_else(|| "invalid delta".to_string())?;
+
+        Ok(CypherText {
+            gamma: (gamma * scalar).compress(),
+            delta: (delta * scalar).compress(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let keys = KeyPair::new().unwrap();
+        let msg = Message::random().unwrap();
+
+        let cyph = encrypt(msg, keys.public_key).unwrap();
+        let decrypted = decrypt(cyph, keys.private_key).unwrap();
+
+        assert_eq!(msg, decrypted);
+    }
+
+    #[test]
+    fn encrypt_decrypt_amount_round_trip() {
+        let keys = KeyPair::new().unwrap();
+        let amount = 424242u64;
+
+        let cyph = encrypt_amount(amount, keys.public_key).unwrap();
+        let decrypted = decrypt_amount(cyph, keys.private_key).unwrap();
+
+        assert_eq!(amount, decrypted);
+    }
+
+    #[test]
+    fn message_serde_round_trip() {
+        let msg = Message::random().unwrap();
+        let json = serde_json::to_string(&msg).unwrap();
+        let decoded: Message = serde_json::from_str(&json).unwrap();
+        assert_eq!(msg, decoded);
+    }
+
+    #[test]
+    fn message_hex_round_trip() {
+        let msg = Message::random().unwrap();
+        let decoded: Message = msg.to_string().parse().unwrap();
+        assert_eq!(msg, decoded);
+    }
+
+    #[test]
+    fn message_from_str_rejects_garbage() {
+        assert!("not hex".parse::<Message>().is_err());
+        assert!(hex::encode([0u8; 16]).parse::<Message>().is_err());
+    }
+
+    #[test]
+    fn private_key_serde_round_trip() {
+        let key = PrivateKey::new().unwrap();
+        let json = serde_json::to_string(&key).unwrap();
+        let decoded: PrivateKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(key, decoded);
+    }
+
+    #[test]
+    fn private_key_from_slice_rejects_non_canonical() {
+        assert!(PrivateKey::from_slice([0xffu8; 32]).is_err());
+    }
+
+    #[test]
+    fn public_key_serde_round_trip() {
+        let keys = KeyPair::new().unwrap();
+        let json = serde_json::to_string(&keys.public_key).unwrap();
+        let decoded: PublicKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(keys.public_key, decoded);
+    }
+
+    #[test]
+    fn public_key_hex_round_trip() {
+        let keys = KeyPair::new().unwrap();
+        let decoded: PublicKey = keys.public_key.to_string().parse().unwrap();
+        assert_eq!(keys.public_key, decoded);
+    }
+
+    #[test]
+    fn public_key_from_str_rejects_garbage() {
+        assert!("not hex".parse::<PublicKey>().is_err());
+        assert!(hex::encode([0u8; 16]).parse::<PublicKey>().is_err());
+    }
+
+    #[test]
+    fn cyphertext_serde_round_trip() {
+        let keys = KeyPair::new().unwrap();
+        let msg = Message::random().unwrap();
+        let cyph = encrypt(msg, keys.public_key).unwrap();
+
+        let json = serde_json::to_string(&cyph).unwrap();
+        let decoded: CypherText = serde_json::from_str(&json).unwrap();
+        assert_eq!(cyph, decoded);
+    }
+
+    #[test]
+    fn cyphertext_hex_round_trip() {
+        let keys = KeyPair::new().unwrap();
+        let msg = Message::random().unwrap();
+        let cyph = encrypt(msg, keys.public_key).unwrap();
+
+        let decoded: CypherText = cyph.to_string().parse().unwrap();
+        assert_eq!(cyph, decoded);
+    }
+
+    #[test]
+    fn cyphertext_from_str_rejects_garbage() {
+        assert!("not hex".parse::<CypherText>().is_err());
+        assert!(hex::encode([0u8; 32]).parse::<CypherText>().is_err());
+    }
+
+    #[test]
+    fn keypair_serde_round_trip() {
+        let keys = KeyPair::new().unwrap();
+        let json = serde_json::to_string(&keys).unwrap();
+        let decoded: KeyPair = serde_json::from_str(&json).unwrap();
+        assert_eq!(keys, decoded);
+    }
+
+    #[test]
+    fn homomorphic_add_and_mul() {
+        let keys = KeyPair::new().unwrap();
+        let a = 111u64;
+        let b = 222u64;
+
+        let cyph_a = encrypt_amount(a, keys.public_key).unwrap();
+        let cyph_b = encrypt_amount(b, keys.public_key).unwrap();
+
+        let summed = (cyph_a + cyph_b).unwrap();
+        assert_eq!(decrypt_amount(summed, keys.private_key.clone()).unwrap(), a + b);
+
+        let scaled = (cyph_a * Scalar::from(3u64)).unwrap();
+        assert_eq!(decrypt_amount(scaled, keys.private_key).unwrap(), a * 3);
+    }
+}
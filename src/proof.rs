@@ -0,0 +1,198 @@
+//! `proof` implements a Fiat-Shamir-transformed Schnorr sigma protocol
+//! letting an encryptor prove that a `CypherText` correctly encrypts a
+//! claimed `Message` under a given `PublicKey`, without revealing the
+//! ephemeral randomness `k` used by `encrypt_with_randomness`.
+
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::ristretto::{RistrettoPoint, CompressedRistretto};
+use curve25519_dalek::constants::{RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_TABLE};
+use digest::Digest;
+use rand_core::{RngCore, CryptoRng, OsRng};
+use sha2::Sha512;
+use subtle::ConstantTimeEq;
+
+use crate::{CypherText, Message, PublicKey};
+
+/// `EncryptionProof` is a zero-knowledge proof that a `CypherText`
+/// encrypts a claimed `Message` under a given `PublicKey`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct EncryptionProof {
+    a: CompressedRistretto,
+    b: CompressedRistretto,
+    z: Scalar,
+}
+
+impl EncryptionProof {
+    /// `to_bytes` serializes the `EncryptionProof` as 96 bytes: `a`, `b`
+    /// and `z`, each 32 bytes, concatenated in that order.
+    pub fn to_bytes(&self) -> [u8; 96] {
+        let mut buf = [0u8; 96];
+        buf[..32].copy_from_slice(self.a.as_bytes());
+        buf[32..64].copy_from_slice(self.b.as_bytes());
+        buf[64..].copy_from_slice(self.z.as_bytes());
+        buf
+    }
+
+    /// `from_slice` deserializes an `EncryptionProof` from 96 bytes, as
+    /// produced by `to_bytes`.
+    pub fn from_slice(buf: &[u8]) -> Result<EncryptionProof, String> {
+        if buf.len() != 96 {
+            return Err("invalid length".into());
+        }
+
+        let a = CompressedRistretto::from_slice(&buf[..32]);
+        let b = CompressedRistretto::from_slice(&buf[32..64]);
+
+        if a.decompress().is_none() {
+            return Err("invalid a".into());
+        }
+        if b.decompress().is_none() {
+            return Err("invalid b".into());
+        }
+
+        let mut z_buf = [0u8; 32];
+        z_buf.copy_from_slice(&buf[64..]);
+        let z = Scalar::from_canonical_bytes(z_buf)
+            .ok_or_else(|| "not canonical bytes".to_string())?;
+
+        Ok(EncryptionProof { a, b, z })
+    }
+}
+
+/// `challenge` derives the Fiat-Shamir challenge `c = H(G, pk, gamma,
+/// delta, msg, A, B)` as a `Scalar::from_hash` over `Sha512`.
+fn challenge(
+    pk: &CompressedRistretto,
+    gamma: &CompressedRistretto,
+    delta: &CompressedRistretto,
+    msg: &CompressedRistretto,
+    a: &CompressedRistretto,
+    b: &CompressedRistretto,
+) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(RISTRETTO_BASEPOINT_COMPRESSED.as_bytes());
+    hasher.update(pk.as_bytes());
+    hasher.update(gamma.as_bytes());
+    hasher.update(delta.as_bytes());
+    hasher.update(msg.as_bytes());
+    hasher.update(a.as_bytes());
+    hasher.update(b.as_bytes());
+    Scalar::from_hash(hasher)
+}
+
+/// `prove_encryption` proves that `cyph` encrypts `msg` under `pk`, given
+/// the ephemeral `Scalar` `k` returned by `encrypt_with_randomness` when
+/// `cyph` was formed.
+pub fn prove_encryption(msg: Message, pk: PublicKey, k: Scalar, cyph: CypherText) -> Result<EncryptionProof, String> {
+    let mut rng = OsRng;
+
+    prove_encryption_with(msg, pk, k, cyph, &mut rng)
+}
+
+/// `prove_encryption_with` is like `prove_encryption`, but requires to
+/// specify a random generator for the prover's commitment scalar `t`.
+pub fn prove_encryption_with<R>(msg: Message, pk: PublicKey, k: Scalar, cyph: CypherText, mut rng: &mut R) -> Result<EncryptionProof, String>
+    where R: RngCore + CryptoRng
+{
+    if let Some(pk_point) = pk.to_point().decompress() {
+        if msg.to_point().decompress().is_some() {
+            if cyph.gamma.decompress().is_some() && cyph.delta.decompress().is_some() {
+                let mut t = Scalar::random(&mut rng);
+                while t.ct_eq(&Scalar::zero()).unwrap_u8() == 1u8 {
+                    t = Scalar::random(&mut rng);
+                }
+
+                let a = (&t * &RISTRETTO_BASEPOINT_TABLE).compress();
+                let b = (pk_point * t).compress();
+
+                let c = challenge(&pk.to_point(), &cyph.gamma, &cyph.delta, &msg.to_point(), &a, &b);
+                let z = t + (c * k);
+
+                Ok(EncryptionProof { a, b, z })
+            } else {
+                Err("invalid ciphertext".into())
+            }
+        } else {
+            Err("invalid message".into())
+        }
+    } else {
+        Err("invalid public key".into())
+    }
+}
+
+/// `verify_encryption` checks an `EncryptionProof` that `cyph` encrypts
+/// `msg` under `pk`, verifying `z*G == A + c*gamma` and
+/// `z*pk == B + c*(delta - msg)`.
+pub fn verify_encryption(cyph: CypherText, msg: Message, pk: PublicKey, proof: &EncryptionProof) -> bool {
+    let pk_point = match pk.to_point().decompress() {
+        Some(point) => point,
+        None => return false,
+    };
+    let gamma_point = match cyph.gamma.decompress() {
+        Some(point) => point,
+        None => return false,
+    };
+    let delta_point = match cyph.delta.decompress() {
+        Some(point) => point,
+        None => return false,
+    };
+    let msg_point = match msg.to_point().decompress() {
+        Some(point) => point,
+        None => return false,
+    };
+    let a_point = match proof.a.decompress() {
+        Some(point) => point,
+        None => return false,
+    };
+    let b_point = match proof.b.decompress() {
+        Some(point) => point,
+        None => return false,
+    };
+
+    let c = challenge(&pk.to_point(), &cyph.gamma, &cyph.delta, &msg.to_point(), &proof.a, &proof.b);
+
+    let lhs_g: RistrettoPoint = &proof.z * &RISTRETTO_BASEPOINT_TABLE;
+    let rhs_g = a_point + (c * gamma_point);
+
+    let lhs_pk = pk_point * proof.z;
+    let rhs_pk = b_point + (c * (delta_point - msg_point));
+
+    lhs_g == rhs_g && lhs_pk == rhs_pk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyPair;
+
+    #[test]
+    fn prove_verify_round_trip() {
+        let keys = KeyPair::new().unwrap();
+        let msg = Message::random().unwrap();
+
+        let mut rng = OsRng;
+        let (cyph, k) = crate::encrypt_with_randomness(msg, keys.public_key, &mut rng).unwrap();
+        let proof = prove_encryption(msg, keys.public_key, k, cyph).unwrap();
+
+        assert!(verify_encryption(cyph, msg, keys.public_key, &proof));
+    }
+
+    #[test]
+    fn verify_rejects_tampering() {
+        let keys = KeyPair::new().unwrap();
+        let msg = Message::random().unwrap();
+
+        let mut rng = OsRng;
+        let (cyph, k) = crate::encrypt_with_randomness(msg, keys.public_key, &mut rng).unwrap();
+        let proof = prove_encryption(msg, keys.public_key, k, cyph).unwrap();
+
+        let other_msg = Message::random().unwrap();
+        assert!(!verify_encryption(cyph, other_msg, keys.public_key, &proof));
+
+        let other_cyph = crate::encrypt(other_msg, keys.public_key).unwrap();
+        assert!(!verify_encryption(other_cyph, msg, keys.public_key, &proof));
+
+        let tampered = EncryptionProof { z: proof.z + Scalar::one(), ..proof };
+        assert!(!verify_encryption(cyph, msg, keys.public_key, &tampered));
+    }
+}
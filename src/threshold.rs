@@ -0,0 +1,213 @@
+//! `threshold` splits a `PrivateKey` into `n` Shamir shares with
+//! reconstruction threshold `t`, and supports distributed ElGamal
+//! decryption without ever reconstructing the joint secret, in the
+//! style of the FROST/SimplPedPoP Ristretto threshold constructions.
+
+use std::collections::HashSet;
+
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::ristretto::{RistrettoPoint, CompressedRistretto};
+use curve25519_dalek::traits::Identity;
+use rand_core::{RngCore, CryptoRng, OsRng};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use std::fmt;
+
+use crate::{CypherText, Message, PrivateKey, PublicKey};
+
+/// `KeyShare` is one participant's share of a Shamir-split `PrivateKey`,
+/// at least as sensitive as the `PrivateKey` it was split from.
+#[derive(Clone, Eq, PartialEq, Zeroize, ZeroizeOnDrop)]
+pub struct KeyShare {
+    pub index: u8,
+    pub scalar: Scalar,
+}
+
+impl fmt::Debug for KeyShare {
+    /// Redacts the scalar, so an errant `{:?}` doesn't leak key material.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KeyShare")
+            .field("index", &self.index)
+            .field("scalar", &"..")
+            .finish()
+    }
+}
+
+/// `PartialDecryption` is one participant's contribution towards
+/// decrypting a `CypherText`, computed from their `KeyShare`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct PartialDecryption {
+    pub index: u8,
+    pub point: CompressedRistretto,
+}
+
+/// `split_key` splits `secret` into `n` `KeyShare`s with reconstruction
+/// threshold `t`, and returns them alongside the joint `PublicKey`.
+pub fn split_key(secret: &PrivateKey, n: u8, t: u8) -> Result<(PublicKey, Vec<KeyShare>), String> {
+    let mut rng = OsRng;
+
+    split_key_with(secret, n, t, &mut rng)
+}
+
+/// `split_key_with` is like `split_key`, but requires to specify a
+/// random generator for the polynomial coefficients.
+pub fn split_key_with<R>(secret: &PrivateKey, n: u8, t: u8, mut rng: &mut R) -> Result<(PublicKey, Vec<KeyShare>), String>
+    where R: RngCore + CryptoRng
+{
+    if t == 0 || n == 0 || t > n {
+        return Err("invalid threshold".into());
+    }
+
+    let mut coefficients = Vec::with_capacity(t as usize);
+    coefficients.push(secret.to_scalar());
+    for _ in 1..t {
+        coefficients.push(Scalar::random(&mut rng));
+    }
+
+    let mut shares = Vec::with_capacity(n as usize);
+    for index in 1..=n {
+        let x = Scalar::from(index as u64);
+        let scalar = evaluate_polynomial(&coefficients, x);
+        shares.push(KeyShare { index, scalar });
+    }
+
+    Ok((secret.to_public(), shares))
+}
+
+/// `evaluate_polynomial` evaluates the polynomial with the given
+/// coefficients (lowest degree first) at `x`, via Horner's method.
+fn evaluate_polynomial(coefficients: &[Scalar], x: Scalar) -> Scalar {
+    let mut result = Scalar::zero();
+    for coefficient in coefficients.iter().rev() {
+        result = (result * x) + coefficient;
+    }
+    result
+}
+
+/// `partial_decrypt` computes a `KeyShare` holder's partial decryption
+/// `sk_i*gamma` of `cyph`.
+pub fn partial_decrypt(cyph: &CypherText, share: &KeyShare) -> Result<PartialDecryption, String> {
+    let gamma_point = cyph.gamma.decompress()
+        .ok_or_else(|| "invalid gamma".to_string())?;
+
+    let point = (gamma_point * share.scalar).compress();
+    Ok(PartialDecryption { index: share.index, point })
+}
+
+/// `combine` reconstructs `secret*gamma` from at least `t` distinct
+/// `PartialDecryption`s via Lagrange interpolation at `x = 0`, and
+/// returns the decrypted `Message`. Guards against duplicate/zero
+/// indices and an insufficient number of shares.
+pub fn combine(cyph: CypherText, partials: &[PartialDecryption], t: usize) -> Result<Message, String> {
+    if partials.len() < t {
+        return Err("insufficient shares".into());
+    }
+
+    let mut seen = HashSet::new();
+    for partial in partials {
+        if partial.index == 0 {
+            return Err("zero index".into());
+        }
+        if !seen.insert(partial.index) {
+            return Err("duplicate index".into());
+        }
+    }
+
+    let delta_point = cyph.delta.decompress()
+        .ok_or_else(|| "invalid delta".to_string())?;
+
+    let mut shared = RistrettoPoint::identity();
+    for partial in partials {
+        let point = partial.point.decompress()
+            .ok_or_else(|| "invalid partial".to_string())?;
+
+        let lambda = lagrange_coefficient(partial.index, partials);
+        shared += point * lambda;
+    }
+
+    let msg_point = delta_point - shared;
+    Ok(Message::from_point(&msg_point.compress()))
+}
+
+/// `lagrange_coefficient` computes `λ_i = Π_{j≠i} x_j/(x_j - x_i)`
+/// evaluated at `x = 0`, for participant `i` among `partials`.
+fn lagrange_coefficient(i: u8, partials: &[PartialDecryption]) -> Scalar {
+    let xi = Scalar::from(i as u64);
+    let mut lambda = Scalar::one();
+
+    for partial in partials {
+        if partial.index == i {
+            continue;
+        }
+
+        let xj = Scalar::from(partial.index as u64);
+        lambda *= xj * (xj - xi).invert();
+    }
+
+    lambda
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Message;
+
+    #[test]
+    fn split_combine_round_trip() {
+        let secret = PrivateKey::new().unwrap();
+        let (public_key, shares) = split_key(&secret, 5, 3).unwrap();
+
+        let msg = Message::random().unwrap();
+        let cyph = crate::encrypt(msg, public_key).unwrap();
+
+        let partials: Vec<PartialDecryption> = shares[..3].iter()
+            .map(|share| partial_decrypt(&cyph, share).unwrap())
+            .collect();
+
+        let decrypted = combine(cyph, &partials, 3).unwrap();
+        assert_eq!(msg, decrypted);
+    }
+
+    #[test]
+    fn combine_rejects_insufficient_shares() {
+        let secret = PrivateKey::new().unwrap();
+        let (public_key, shares) = split_key(&secret, 5, 3).unwrap();
+
+        let msg = Message::random().unwrap();
+        let cyph = crate::encrypt(msg, public_key).unwrap();
+
+        let partials: Vec<PartialDecryption> = shares[..2].iter()
+            .map(|share| partial_decrypt(&cyph, share).unwrap())
+            .collect();
+
+        assert_eq!(combine(cyph, &partials, 3), Err("insufficient shares".into()));
+    }
+
+    #[test]
+    fn combine_rejects_duplicate_index() {
+        let secret = PrivateKey::new().unwrap();
+        let (public_key, shares) = split_key(&secret, 5, 3).unwrap();
+
+        let msg = Message::random().unwrap();
+        let cyph = crate::encrypt(msg, public_key).unwrap();
+
+        let partial = partial_decrypt(&cyph, &shares[0]).unwrap();
+        let partials = vec![partial, partial, partial];
+
+        assert_eq!(combine(cyph, &partials, 3), Err("duplicate index".into()));
+    }
+
+    #[test]
+    fn combine_rejects_zero_index() {
+        let secret = PrivateKey::new().unwrap();
+        let (public_key, _) = split_key(&secret, 5, 3).unwrap();
+
+        let msg = Message::random().unwrap();
+        let cyph = crate::encrypt(msg, public_key).unwrap();
+
+        let zero_share = KeyShare { index: 0, scalar: secret.to_scalar() };
+        let partial = partial_decrypt(&cyph, &zero_share).unwrap();
+        let partials = vec![partial, partial, partial];
+
+        assert_eq!(combine(cyph, &partials, 3), Err("zero index".into()));
+    }
+}
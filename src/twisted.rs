@@ -0,0 +1,197 @@
+//! `twisted` implements the twisted ElGamal scheme from Solana's
+//! zk-token-sdk, which separates the Pedersen commitment to a message
+//! from the per-recipient decryption handle. Unlike plain ElGamal, the
+//! commitment `C` does not depend on the recipient's key, so several
+//! decryption handles can point to the same commitment.
+
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::ristretto::{RistrettoPoint, CompressedRistretto};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use digest::Digest;
+use rand_core::{RngCore, CryptoRng, OsRng};
+use sha2::Sha512;
+use subtle::ConstantTimeEq;
+
+use crate::{PrivateKey, DEFAULT_AMOUNT_BOUND};
+
+lazy_static! {
+    /// `H` is the second Ristretto generator used for Pedersen
+    /// commitments, derived as a Nothing-Up-My-Sleeve point via
+    /// `RistrettoPoint::from_hash` over a domain-separated label,
+    /// distinct from the basepoint `G` used elsewhere in this crate.
+    static ref H: RistrettoPoint =
+        RistrettoPoint::from_hash(Sha512::new().chain(b"elgamal-curve25519 twisted ElGamal generator H"));
+}
+
+/// `TwistedPrivateKey` is a twisted ElGamal private key. It wraps the same
+/// `PrivateKey` scalar used elsewhere in this crate, so it inherits its
+/// key-generation machinery.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct TwistedPrivateKey(PrivateKey);
+
+impl TwistedPrivateKey {
+    /// `new` creates a new random `TwistedPrivateKey`.
+    pub fn new() -> Result<TwistedPrivateKey, String> {
+        let private = PrivateKey::new()?;
+        Ok(TwistedPrivateKey(private))
+    }
+
+    /// `from_rng` creates a new random `TwistedPrivateKey`, but requires
+    /// to specify a random generator.
+    pub fn from_rng<R>(rng: &mut R) -> Result<TwistedPrivateKey, String>
+        where R: RngCore + CryptoRng
+    {
+        let private = PrivateKey::from_rng(rng)?;
+        Ok(TwistedPrivateKey(private))
+    }
+
+    /// `to_public` returns the `TwistedPublicKey` of the `TwistedPrivateKey`,
+    /// computed as `pk = s⁻¹·H`.
+    pub fn to_public(&self) -> TwistedPublicKey {
+        let inverse = self.0.to_scalar().invert();
+        let point = inverse * *H;
+        TwistedPublicKey(point.compress())
+    }
+}
+
+/// `TwistedPublicKey` is a twisted ElGamal public key, used to compute a
+/// decryption handle pointing at a Pedersen commitment.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct TwistedPublicKey(CompressedRistretto);
+
+impl TwistedPublicKey {
+    /// `from_point` creates a new `TwistedPublicKey` from a `CompressedRistretto`.
+    pub fn from_point(point: CompressedRistretto) -> TwistedPublicKey {
+        TwistedPublicKey(point)
+    }
+
+    /// `to_point` returns the inner `CompressedRistretto` of the `TwistedPublicKey`.
+    pub fn to_point(&self) -> CompressedRistretto {
+        self.0
+    }
+}
+
+/// `TwistedKeyPair` is a pair of twisted ElGamal `TwistedPublicKey` and
+/// `TwistedPrivateKey`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct TwistedKeyPair {
+    pub public_key: TwistedPublicKey,
+    pub private_key: TwistedPrivateKey,
+}
+
+impl TwistedKeyPair {
+    /// `new` creates a new random `TwistedKeyPair`.
+    pub fn new() -> Result<TwistedKeyPair, String> {
+        let private_key = TwistedPrivateKey::new()?;
+        let public_key = private_key.to_public();
+
+        let keys = TwistedKeyPair { public_key, private_key };
+        Ok(keys)
+    }
+}
+
+/// `TwistedCypherText` is a twisted ElGamal ciphertext: a Pedersen
+/// commitment to an amount, plus a decryption handle for one recipient.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct TwistedCypherText {
+    pub commitment: CompressedRistretto,
+    pub handle: CompressedRistretto,
+}
+
+/// `encrypt_amount` commits to a `u64` amount and computes a decryption
+/// handle for `pk`, sampling a fresh nonzero blinding scalar `r` from
+/// `OsRng`.
+pub fn encrypt_amount(amount: u64, pk: TwistedPublicKey) -> Result<TwistedCypherText, String> {
+    let mut rng = OsRng;
+
+    encrypt_amount_with(amount, pk, &mut rng)
+}
+
+/// `encrypt_amount_with` is like `encrypt_amount`, but requires to
+/// specify a random generator for the blinding scalar `r`.
+pub fn encrypt_amount_with<R>(amount: u64, pk: TwistedPublicKey, rng: &mut R) -> Result<TwistedCypherText, String>
+    where R: RngCore + CryptoRng
+{
+    let (cyph, _r) = encrypt_amount_with_randomness(amount, pk, rng)?;
+    Ok(cyph)
+}
+
+/// `encrypt_amount_with_randomness` is like `encrypt_amount_with`, but
+/// also returns the blinding scalar `r` it sampled. A caller holding `r`
+/// can pass it to `handle_for` to compute a second decryption handle
+/// against the same commitment, for another recipient's `TwistedPublicKey`,
+/// without re-blinding the amount.
+pub fn encrypt_amount_with_randomness<R>(amount: u64, pk: TwistedPublicKey, mut rng: &mut R) -> Result<(TwistedCypherText, Scalar), String>
+    where R: RngCore + CryptoRng
+{
+    if pk.to_point().decompress().is_none() {
+        return Err("invalid public key".into());
+    }
+
+    let mut r = Scalar::random(&mut rng);
+    while r.ct_eq(&Scalar::zero()).unwrap_u8() == 1u8 {
+        r = Scalar::random(&mut rng);
+    }
+
+    let commitment = (&Scalar::from(amount) * &RISTRETTO_BASEPOINT_TABLE) + (r * *H);
+    let handle = handle_for(r, pk)?;
+
+    let cyph = TwistedCypherText {
+        commitment: commitment.compress(),
+        handle,
+    };
+    Ok((cyph, r))
+}
+
+/// `handle_for` computes a decryption handle for `pk` from the blinding
+/// scalar `r` returned by `encrypt_amount_with_randomness`, so the same
+/// commitment can carry a second handle for another recipient.
+pub fn handle_for(r: Scalar, pk: TwistedPublicKey) -> Result<CompressedRistretto, String> {
+    let pk_point = pk.to_point().decompress()
+        .ok_or_else(|| "invalid public key".to_string())?;
+
+    Ok((r * pk_point).compress())
+}
+
+/// `decrypt_amount` recovers the `u64` amount committed to by a
+/// `TwistedCypherText`, computing `C - s·D = amount·G` and solving the
+/// discrete log via the same baby-step giant-step solver used by
+/// `decrypt_amount` on plain `CypherText`s.
+pub fn decrypt_amount(cyph: TwistedCypherText, sk: TwistedPrivateKey) -> Option<u64> {
+    let commitment = cyph.commitment.decompress()?;
+    let handle = cyph.handle.decompress()?;
+
+    let amount_point = commitment - (sk.0.to_scalar() * handle);
+    crate::solve_discrete_log_bounded(amount_point, DEFAULT_AMOUNT_BOUND)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_amount_round_trip() {
+        let keys = TwistedKeyPair::new().unwrap();
+        let amount = 424242u64;
+
+        let cyph = encrypt_amount(amount, keys.public_key).unwrap();
+        let decrypted = decrypt_amount(cyph, keys.private_key).unwrap();
+
+        assert_eq!(amount, decrypted);
+    }
+
+    #[test]
+    fn second_handle_decrypts_same_commitment_for_other_recipient() {
+        let sender = TwistedKeyPair::new().unwrap();
+        let other = TwistedKeyPair::new().unwrap();
+        let amount = 424242u64;
+
+        let mut rng = OsRng;
+        let (cyph, r) = encrypt_amount_with_randomness(amount, sender.public_key, &mut rng).unwrap();
+        let other_handle = handle_for(r, other.public_key).unwrap();
+        let other_cyph = TwistedCypherText { commitment: cyph.commitment, handle: other_handle };
+
+        assert_eq!(amount, decrypt_amount(cyph, sender.private_key).unwrap());
+        assert_eq!(amount, decrypt_amount(other_cyph, other.private_key).unwrap());
+    }
+}